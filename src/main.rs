@@ -1,12 +1,12 @@
 use std::{
     collections::HashMap,
     fs::{self, OpenOptions},
-    io::Write,
+    io::{Read, Write},
     process::exit,
     str::FromStr,
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     thread,
     time::{Duration, Instant},
@@ -15,8 +15,17 @@ use std::{
 use chrono::Local;
 use clap::{arg, command, Parser, ValueEnum};
 use colored::{ColoredString, Colorize};
-use reqwest::{Body, Client, Method, Request, Url};
-use tokio::{task, time};
+use flate2::read::{DeflateDecoder, GzDecoder};
+use rand::Rng;
+use regex::Regex;
+use reqwest::{
+    header::{HeaderMap, CONTENT_ENCODING, CONTENT_RANGE, RANGE},
+    Body, Certificate, Client, ClientBuilder, Identity, Method, Request, Url,
+};
+use serde::Serialize;
+use tokio::{sync::Semaphore, task, time};
+
+const BODY_LOG_PREVIEW_BYTES: usize = 200;
 
 #[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
@@ -24,18 +33,30 @@ struct Args {
     #[arg(short, long)]
     addr: String,
 
-    #[arg(short, long, default_value_t = 25)]
+    #[arg(short, long, default_value_t = 25, conflicts_with = "duration")]
     count: u32,
 
+    #[arg(long, conflicts_with = "count")]
+    duration: Option<u64>,
+
+    #[arg(long, default_value_t = 25)]
+    concurrency: usize,
+
+    #[arg(long)]
+    rate: Option<f64>,
+
+    // Removed in favor of --rate (open-loop) / --concurrency (closed-loop). Kept as a
+    // hidden flag so old invocations fail with a clear message instead of "unexpected
+    // argument" or, worse, silently being ignored.
+    #[arg(short, long, hide = true)]
+    delay: Option<u32>,
+
     #[arg(short, long, default_value_t = HttpMethod::GET)]
     method: HttpMethod,
 
     #[arg(short, long)]
     body: Option<String>,
 
-    #[arg(short, long, default_value_t = 0)]
-    delay: u32,
-
     #[arg(short, long = "expected", default_value_t = 200)]
     expected_code: u16,
 
@@ -44,95 +65,362 @@ struct Args {
 
     #[arg(short = 'H', long)]
     headers: Option<Vec<String>>,
+
+    #[arg(long, default_value_t = false)]
+    insecure: bool,
+
+    #[arg(long)]
+    cacert: Option<String>,
+
+    #[arg(long = "client-cert")]
+    client_cert: Option<String>,
+
+    #[arg(long = "client-key")]
+    client_key: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    native_certs: bool,
+
+    #[arg(long = "expect-body")]
+    expect_body: Option<String>,
+
+    #[arg(long = "expect-body-regex")]
+    expect_body_regex: Option<String>,
+
+    #[arg(long)]
+    timeout: Option<u64>,
+
+    #[arg(long, default_value_t = 0)]
+    retries: u32,
+
+    #[arg(long, default_value_t = ReportFormat::Text)]
+    report: ReportFormat,
+
+    #[arg(long = "report-file")]
+    report_file: Option<String>,
+
+    #[arg(long = "fail-on-error-rate")]
+    fail_on_error_rate: Option<f64>,
+
+    #[arg(long, default_value_t = false)]
+    range: bool,
+
+    #[arg(long = "range-chunk", default_value_t = 65_536)]
+    range_chunk: u64,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl ToString for ReportFormat {
+    fn to_string(&self) -> String {
+        match self {
+            ReportFormat::Text => "text".into(),
+            ReportFormat::Json => "json".into(),
+            ReportFormat::Csv => "csv".into(),
+        }
+    }
+}
+
+impl Args {
+    fn is_text_report(&self) -> bool {
+        matches!(self.report, ReportFormat::Text)
+    }
+}
+
+enum LoadMode {
+    Count(u32),
+    Duration(Duration),
+}
+
+struct Counters {
+    successes: AtomicUsize,
+    fails: AtomicUsize,
+    timeouts: AtomicUsize,
+}
+
+impl Counters {
+    fn new() -> Self {
+        Self {
+            successes: AtomicUsize::new(0),
+            fails: AtomicUsize::new(0),
+            timeouts: AtomicUsize::new(0),
+        }
+    }
+}
+
+struct RangeCursor {
+    offset: u64,
+    total: Option<u64>,
+}
+
+impl RangeCursor {
+    fn new() -> Self {
+        Self {
+            offset: 0,
+            total: None,
+        }
+    }
+}
+
+const RETRY_BASE_DELAY_MS: u64 = 100;
+const RETRY_MAX_DELAY_MS: u64 = 5_000;
+
+fn retry_backoff(attempt: u32) -> Duration {
+    let exponential = RETRY_BASE_DELAY_MS.saturating_mul(1u64 << attempt.min(16));
+    let capped = exponential.min(RETRY_MAX_DELAY_MS);
+    let jitter = rand::thread_rng().gen_range(0..=capped / 10 + 1);
+    Duration::from_millis(capped + jitter)
 }
 
 #[tokio::main]
 async fn main() {
     let prefix = "[>]".blue().bold();
     let args = Args::parse();
-    let count = args.count;
+
+    if args.delay.is_some() {
+        eprintln!(
+            "--delay was removed; use --rate <requests-per-second> for open-loop pacing, \
+             or --concurrency for closed-loop pacing."
+        );
+        exit(1);
+    }
+
+    if let Some(rate) = args.rate {
+        if !rate.is_finite() || rate <= 0.0 {
+            eprintln!("Invalid --rate: must be greater than 0 (got {})", rate);
+            exit(1);
+        }
+    }
+
+    let expect_body_regex = Arc::new(compile_expect_body_regex(&args));
+    let load_mode = match args.duration {
+        Some(secs) => LoadMode::Duration(Duration::from_secs(secs)),
+        None => LoadMode::Count(args.count),
+    };
     let mut tasks = vec![];
     let timer_seconds = 3;
-    let client = Arc::new(Client::new());
+    let client = Arc::new(build_client(&args));
 
     clear_log(args.logs);
 
     let request = build_request(args.clone(), prefix.clone());
 
-    for i in (1..=timer_seconds).rev() {
-        print!(
-            "{prefix} Going to send {} requests to {}, in {} seconds\r",
-            count.to_string().blue(),
+    let load_description = match load_mode {
+        LoadMode::Count(count) => format!("{} requests", count.to_string().blue()),
+        LoadMode::Duration(duration) => format!("requests for {} seconds", duration.as_secs().to_string().blue()),
+    };
+
+    if args.is_text_report() {
+        for i in (1..=timer_seconds).rev() {
+            print!(
+                "{prefix} Going to send {} to {}, in {} seconds\r",
+                load_description,
+                request.url().to_string().blue(),
+                i.to_string().blue().bold()
+            );
+            std::io::stdout().flush().unwrap();
+            thread::sleep(Duration::from_secs(1));
+        }
+
+        println!(
+            "{prefix} Going to send: {} to: {}, {}",
+            load_description,
             request.url().to_string().blue(),
-            i.to_string().blue().bold()
+            "has started..".blue().bold()
         );
-        std::io::stdout().flush().unwrap();
-        thread::sleep(Duration::from_secs(1));
-    }
 
-    println!(
-        "{prefix} Going to send: {} requests to: {}, {}",
-        count.to_string().blue(),
-        request.url().to_string().blue(),
-        "has started..".blue().bold()
-    );
+        println!("{prefix} Waiting for requests to finish");
+    }
 
-    println!("{prefix} Waiting for requests to finish");
-    let successes = Arc::new(AtomicUsize::new(0));
-    let fails = Arc::new(AtomicUsize::new(0));
+    let counters = Arc::new(Counters::new());
+    // Count mode knows its exact sample count up front; duration mode estimates from
+    // --rate (or falls back to a concurrency-scaled guess) so the hot path still only
+    // pushes instead of reallocating.
+    let latency_capacity = match load_mode {
+        LoadMode::Count(count) => count as usize,
+        LoadMode::Duration(duration) => match args.rate {
+            Some(rps) => (rps * duration.as_secs_f64()).ceil() as usize,
+            None => args.concurrency.max(1) * 16,
+        },
+    };
+    let latencies = Arc::new(Mutex::new(Vec::<Duration>::with_capacity(latency_capacity)));
+    let semaphore = Arc::new(Semaphore::new(args.concurrency.max(1)));
+    let mut rate_interval = args.rate.map(|rps| time::interval(Duration::from_secs_f64(1.0 / rps)));
+    // Sized 1:1 with concurrency so a cursor is always available to pop whenever a
+    // semaphore permit is acquired, and each in-flight task owns its cursor exclusively
+    // until it pushes it back — no two tasks can ever share one.
+    let range_cursor_pool: Arc<Mutex<Vec<Arc<Mutex<RangeCursor>>>>> = Arc::new(Mutex::new(
+        (0..args.concurrency.max(1))
+            .map(|_| Arc::new(Mutex::new(RangeCursor::new())))
+            .collect(),
+    ));
 
     let start_time = Instant::now();
-    for _ in 0..count {
+    let mut spawned: u32 = 0;
+    loop {
+        let should_continue = match load_mode {
+            LoadMode::Count(count) => spawned < count,
+            LoadMode::Duration(duration) => start_time.elapsed() < duration,
+        };
+        if !should_continue {
+            break;
+        }
+
+        if let Some(interval) = rate_interval.as_mut() {
+            interval.tick().await;
+        }
+
+        // True open-loop pacing means arrivals never wait on in-flight requests: if
+        // --rate is set, skip the concurrency gate entirely so a slow server causes
+        // queue buildup instead of silently capping the rate at the concurrency limit.
+        // Range mode keeps the gate regardless, since its cursor pool is sized 1:1 with
+        // `concurrency` and assumes that many in-flight requests at most.
+        let permit = if args.rate.is_some() && !args.range {
+            None
+        } else {
+            Some(Arc::clone(&semaphore).acquire_owned().await.unwrap())
+        };
+        spawned += 1;
+
         let prefix = prefix.clone();
-        let successes = Arc::clone(&successes);
-        let fails = Arc::clone(&fails);
+        let counters = Arc::clone(&counters);
         let client = Arc::clone(&client);
-        let request = build_request(args.clone(), prefix.clone());
-
-        if args.delay != 0 {
-            time::sleep(Duration::from_millis(args.delay.into())).await;
+        let latencies = Arc::clone(&latencies);
+        let expect_body_regex = Arc::clone(&expect_body_regex);
+        let args = args.clone();
+
+        if args.range {
+            let cursor_pool = Arc::clone(&range_cursor_pool);
+            tasks.push(task::spawn(async move {
+                let _permit = permit;
+                let cursor = cursor_pool
+                    .lock()
+                    .unwrap()
+                    .pop()
+                    .expect("cursor pool is sized to match concurrency");
+                let request_start = Instant::now();
+                let ok = run_range_request(&client, &args, &prefix, &cursor).await;
+                latencies.lock().unwrap().push(request_start.elapsed());
+                cursor_pool.lock().unwrap().push(cursor);
+
+                if ok {
+                    counters.successes.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    counters.fails.fetch_add(1, Ordering::Relaxed);
+                    if args.is_text_report() {
+                        println!("{prefix} Range check failed (see logs for more)");
+                    }
+                }
+            }));
+            continue;
         }
 
         tasks.push(task::spawn(async move {
-            match client.execute(request).await {
+            let _permit = permit;
+
+            let mut attempt = 0;
+            let (outcome, attempt_elapsed) = loop {
+                let attempt_start = Instant::now();
+                let request = build_request(args.clone(), prefix.clone());
+                match client.execute(request).await {
+                    Ok(response) => break (Ok(response), attempt_start.elapsed()),
+                    Err(why) => {
+                        if attempt >= args.retries {
+                            break (Err(why), attempt_start.elapsed());
+                        }
+
+                        let backoff = retry_backoff(attempt);
+                        log_to_file(
+                            LogLevel::Info(
+                                format!(
+                                    "Retrying after failure (attempt {}/{}, waiting {} ms): {}",
+                                    attempt + 1,
+                                    args.retries,
+                                    backoff.as_millis(),
+                                    why
+                                )
+                                .as_str(),
+                            ),
+                            args.logs,
+                        );
+                        time::sleep(backoff).await;
+                        attempt += 1;
+                    }
+                }
+            };
+
+            // Only the final attempt's latency is recorded, so retry backoff sleeps
+            // never leak into the reported distribution.
+            latencies.lock().unwrap().push(attempt_elapsed);
+
+            match outcome {
                 Ok(response) => {
-                    if response.status().as_u16() != args.expected_code {
-                        fails.fetch_add(1, Ordering::Relaxed);
-                        println!(
-                            "{prefix} Unexpected Status (see logs for more): {}",
-                            response.status().to_string().red()
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    let body_bytes = response.bytes().await.unwrap_or_default();
+                    let body = decode_body(&headers, &body_bytes);
+
+                    let status_ok = status.as_u16() == args.expected_code;
+                    let body_ok = body_matches(&args, &expect_body_regex, &body);
+
+                    if status_ok && body_ok {
+                        counters.successes.fetch_add(1, Ordering::Relaxed);
+                        log_to_file(
+                            LogLevel::Info(format!("Got Response (as expected): {}", status).as_str()),
+                            args.logs,
                         );
+                    } else {
+                        counters.fails.fetch_add(1, Ordering::Relaxed);
+                        if args.is_text_report() {
+                            println!(
+                                "{prefix} Unexpected Result (see logs for more): {}",
+                                status.to_string().red()
+                            );
+                        }
 
                         log_to_file(
                             LogLevel::Error(
                                 format!(
-                                    "Got Unexpected Code (Expected: {}): {}, text: {}",
+                                    "Got Unexpected Result (Expected status {}, body match: {}): {}, body: {}",
                                     args.expected_code,
-                                    response.status(),
-                                    response.text().await.unwrap_or("None".into()),
+                                    body_ok,
+                                    status,
+                                    body.chars().take(BODY_LOG_PREVIEW_BYTES).collect::<String>(),
                                 )
                                 .as_str(),
                             ),
                             args.logs,
                         );
+                    }
+                }
+                Err(why) => {
+                    if why.is_timeout() {
+                        counters.timeouts.fetch_add(1, Ordering::Relaxed);
+                        if args.is_text_report() {
+                            println!("{prefix} Request timed out: {}", why.to_string().red());
+                        }
+                        log_to_file(
+                            LogLevel::Error(format!("Request timed out: {}", why).as_str()),
+                            args.logs,
+                        );
                     } else {
-                        successes.fetch_add(1, Ordering::Relaxed);
+                        counters.fails.fetch_add(1, Ordering::Relaxed);
+                        if args.is_text_report() {
+                            println!("{prefix} Request failed: {}", why.to_string().red());
+                        }
                         log_to_file(
-                            LogLevel::Info(format!("Got Response (as expected): {}", response.status()).as_str()),
+                            LogLevel::Error(
+                                format!("Sending request failed: {}", why.to_string()).as_str(),
+                            ),
                             args.logs,
                         );
                     }
                 }
-                Err(why) => {
-                    fails.fetch_add(1, Ordering::Relaxed);
-                    println!("{prefix} Request failed: {}", why.to_string().red());
-                    log_to_file(
-                        LogLevel::Error(
-                            format!("Sending request failed: {}", why.to_string()).as_str(),
-                        ),
-                        args.logs,
-                    );
-                }
             }
         }));
     }
@@ -142,25 +430,211 @@ async fn main() {
     }
 
     let elapsed_time = start_time.elapsed().as_millis();
-    let fails = fails.load(Ordering::Relaxed).to_string();
-    let successes = successes.load(Ordering::Relaxed).to_string();
-    println!(
-        "{prefix} Done ({} ms)! Successes: {}, Fails: {}",
-        elapsed_time.to_string().blue(),
-        successes.to_string().green(),
-        fails.to_string().red()
-    );
+    let elapsed_secs = start_time.elapsed().as_secs_f64();
+    let success_count = counters.successes.load(Ordering::Relaxed);
+    let fail_count = counters.fails.load(Ordering::Relaxed);
+    let timeout_count = counters.timeouts.load(Ordering::Relaxed);
+    let total_count = success_count + fail_count + timeout_count;
+
+    if args.is_text_report() {
+        println!(
+            "{prefix} Done ({} ms)! Successes: {}, Fails: {}, Timeouts: {}",
+            elapsed_time.to_string().blue(),
+            success_count.to_string().green(),
+            fail_count.to_string().red(),
+            timeout_count.to_string().yellow()
+        );
+    }
 
     log_to_file(
         LogLevel::Info(
             format!(
-                "Done ({} ms)! Successes: {}, Fails: {}",
-                elapsed_time, successes, fails
+                "Done ({} ms)! Successes: {}, Fails: {}, Timeouts: {}",
+                elapsed_time, success_count, fail_count, timeout_count
             )
             .as_str(),
         ),
         args.logs,
     );
+
+    let mut samples = Arc::try_unwrap(latencies)
+        .map(|mutex| mutex.into_inner().unwrap())
+        .unwrap_or_default();
+    let stats = calculate_latency_stats(&mut samples, elapsed_secs);
+
+    if let Some(stats) = &stats {
+        if args.is_text_report() {
+            println!(
+                "{prefix} Latency (ms): min {}, mean {}, p50 {}, p90 {}, p99 {}, max {} | throughput: {} req/s",
+                format_millis(stats.min).blue(),
+                format_millis(stats.mean).blue(),
+                format_millis(stats.p50).blue(),
+                format_millis(stats.p90).blue(),
+                format_millis(stats.p99).blue(),
+                format_millis(stats.max).blue(),
+                format!("{:.2}", stats.throughput).blue()
+            );
+        }
+
+        log_to_file(
+            LogLevel::Info(
+                format!(
+                    "Latency (ms): min {}, mean {}, p50 {}, p90 {}, p99 {}, max {} | throughput: {:.2} req/s",
+                    format_millis(stats.min),
+                    format_millis(stats.mean),
+                    format_millis(stats.p50),
+                    format_millis(stats.p90),
+                    format_millis(stats.p99),
+                    format_millis(stats.max),
+                    stats.throughput
+                )
+                .as_str(),
+            ),
+            args.logs,
+        );
+    }
+
+    let report = StressReport {
+        target: request.url().to_string(),
+        method: args.method.to_string(),
+        total: total_count,
+        success: success_count,
+        fail: fail_count,
+        timeout: timeout_count,
+        elapsed_ms: elapsed_time,
+        latency_min_ms: stats.as_ref().map(|s| millis(s.min)),
+        latency_mean_ms: stats.as_ref().map(|s| millis(s.mean)),
+        latency_p50_ms: stats.as_ref().map(|s| millis(s.p50)),
+        latency_p90_ms: stats.as_ref().map(|s| millis(s.p90)),
+        latency_p99_ms: stats.as_ref().map(|s| millis(s.p99)),
+        latency_max_ms: stats.as_ref().map(|s| millis(s.max)),
+        throughput_rps: stats.as_ref().map(|s| s.throughput),
+    };
+
+    emit_report(&args, &report);
+
+    if let Some(threshold) = args.fail_on_error_rate {
+        let error_rate = if total_count == 0 {
+            0.0
+        } else {
+            (fail_count + timeout_count) as f64 / total_count as f64 * 100.0
+        };
+
+        if error_rate > threshold {
+            eprintln!(
+                "{prefix} Error rate {:.2}% exceeds threshold {:.2}%",
+                error_rate, threshold
+            );
+            exit(1);
+        }
+    }
+}
+
+struct LatencyStats {
+    min: Duration,
+    mean: Duration,
+    p50: Duration,
+    p90: Duration,
+    p99: Duration,
+    max: Duration,
+    throughput: f64,
+}
+
+fn calculate_latency_stats(samples: &mut [Duration], elapsed_secs: f64) -> Option<LatencyStats> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    samples.sort();
+    let n = samples.len();
+    let percentile = |q: f64| samples[(q * (n - 1) as f64).round() as usize];
+    let sum: Duration = samples.iter().sum();
+
+    Some(LatencyStats {
+        min: samples[0],
+        mean: sum / n as u32,
+        p50: percentile(0.50),
+        p90: percentile(0.90),
+        p99: percentile(0.99),
+        max: samples[n - 1],
+        throughput: n as f64 / elapsed_secs,
+    })
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{:.2}", duration.as_secs_f64() * 1000.0)
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[derive(Serialize)]
+struct StressReport {
+    target: String,
+    method: String,
+    total: usize,
+    success: usize,
+    fail: usize,
+    timeout: usize,
+    elapsed_ms: u128,
+    latency_min_ms: Option<f64>,
+    latency_mean_ms: Option<f64>,
+    latency_p50_ms: Option<f64>,
+    latency_p90_ms: Option<f64>,
+    latency_p99_ms: Option<f64>,
+    latency_max_ms: Option<f64>,
+    throughput_rps: Option<f64>,
+}
+
+/// Quotes a CSV field per RFC 4180: wraps it in double quotes and doubles any
+/// embedded double quotes. Needed for fields that can contain arbitrary text
+/// (e.g. a URL with a query string), unlike the other report fields which are
+/// numbers or a fixed set of HTTP method names.
+fn csv_quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+impl StressReport {
+    fn to_csv(&self) -> String {
+        let cell = |value: Option<f64>| value.map(|v| format!("{:.2}", v)).unwrap_or_default();
+
+        format!(
+            "target,method,total,success,fail,timeout,elapsed_ms,latency_min_ms,latency_mean_ms,latency_p50_ms,latency_p90_ms,latency_p99_ms,latency_max_ms,throughput_rps\n{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            csv_quote(&self.target),
+            self.method,
+            self.total,
+            self.success,
+            self.fail,
+            self.timeout,
+            self.elapsed_ms,
+            cell(self.latency_min_ms),
+            cell(self.latency_mean_ms),
+            cell(self.latency_p50_ms),
+            cell(self.latency_p90_ms),
+            cell(self.latency_p99_ms),
+            cell(self.latency_max_ms),
+            cell(self.throughput_rps),
+        )
+    }
+}
+
+fn emit_report(args: &Args, report: &StressReport) {
+    let rendered = match args.report {
+        ReportFormat::Text => return,
+        ReportFormat::Json => serde_json::to_string_pretty(report).unwrap(),
+        ReportFormat::Csv => report.to_csv(),
+    };
+
+    match &args.report_file {
+        Some(path) => {
+            if let Err(why) = fs::write(path, &rendered) {
+                eprintln!("Failed to write report file '{}': {}", path, why);
+                exit(1);
+            }
+        }
+        None => println!("{}", rendered),
+    }
 }
 
 enum LogLevel<'a> {
@@ -177,6 +651,232 @@ impl ToString for LogLevel<'_> {
     }
 }
 
+async fn run_range_request(
+    client: &Client,
+    args: &Args,
+    prefix: &ColoredString,
+    cursor: &Mutex<RangeCursor>,
+) -> bool {
+    let offset = cursor.lock().unwrap().offset;
+    let end = offset + args.range_chunk - 1;
+
+    let mut request = build_request(args.clone(), prefix.clone());
+    request.headers_mut().insert(
+        RANGE,
+        reqwest::header::HeaderValue::from_str(&format!("bytes={}-{}", offset, end)).unwrap(),
+    );
+
+    let response = match client.execute(request).await {
+        Ok(response) => response,
+        Err(why) => {
+            log_to_file(
+                LogLevel::Error(format!("Range request failed: {}", why).as_str()),
+                args.logs,
+            );
+            return false;
+        }
+    };
+
+    let status = response.status();
+    if status.as_u16() != 206 {
+        log_to_file(
+            LogLevel::Error(
+                format!(
+                    "Expected 206 Partial Content for range bytes={}-{}, got: {}",
+                    offset, end, status
+                )
+                .as_str(),
+            ),
+            args.logs,
+        );
+        return false;
+    }
+
+    let content_range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let Some((start, end_received, total)) = content_range.as_deref().and_then(parse_content_range) else {
+        log_to_file(
+            LogLevel::Error(
+                format!("Missing or garbled Content-Range header: {:?}", content_range).as_str(),
+            ),
+            args.logs,
+        );
+        return false;
+    };
+
+    let body_len = response.bytes().await.map(|body| body.len() as u64).unwrap_or(0);
+    let is_final_chunk = total.is_some_and(|total| end_received + 1 >= total);
+    let expected_len = match total {
+        Some(total) if is_final_chunk => total - start,
+        _ => args.range_chunk,
+    };
+
+    if start != offset || body_len != expected_len {
+        log_to_file(
+            LogLevel::Error(
+                format!(
+                    "Range mismatch: expected start {} len {}, got start {} len {} (Content-Range: {:?})",
+                    offset, expected_len, start, body_len, content_range
+                )
+                .as_str(),
+            ),
+            args.logs,
+        );
+        return false;
+    }
+
+    let mut cursor = cursor.lock().unwrap();
+    cursor.total = total;
+    let next_offset = offset + body_len;
+    cursor.offset = match total {
+        Some(total) if next_offset >= total => 0,
+        _ => next_offset,
+    };
+
+    true
+}
+
+fn parse_content_range(value: &str) -> Option<(u64, u64, Option<u64>)> {
+    let rest = value.strip_prefix("bytes ")?;
+    let (range_part, total_part) = rest.split_once('/')?;
+    let (start_str, end_str) = range_part.split_once('-')?;
+
+    let start: u64 = start_str.trim().parse().ok()?;
+    let end: u64 = end_str.trim().parse().ok()?;
+    let total = match total_part.trim() {
+        "*" => None,
+        total_str => Some(total_str.parse().ok()?),
+    };
+
+    Some((start, end, total))
+}
+
+fn load_native_certs() -> Vec<Certificate> {
+    match rustls_native_certs::load_native_certs() {
+        Ok(certs) => certs
+            .into_iter()
+            .filter_map(|cert| Certificate::from_der(cert.as_ref()).ok())
+            .collect(),
+        Err(why) => {
+            eprintln!("Failed to load native root certificates: {}", why);
+            exit(1);
+        }
+    }
+}
+
+fn build_client(args: &Args) -> Client {
+    let mut builder = ClientBuilder::new();
+
+    if args.insecure {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    if args.native_certs {
+        for cert in load_native_certs() {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if let Some(cacert) = &args.cacert {
+        let pem = fs::read(cacert).unwrap_or_else(|why| {
+            eprintln!("Failed to read CA certificate '{}': {}", cacert, why);
+            exit(1);
+        });
+        let cert = Certificate::from_pem(&pem).unwrap_or_else(|why| {
+            eprintln!("Failed to parse CA certificate '{}': {}", cacert, why);
+            exit(1);
+        });
+        builder = builder.add_root_certificate(cert);
+    }
+
+    match (&args.client_cert, &args.client_key) {
+        (Some(cert_path), Some(key_path)) => {
+            let cert_pem = fs::read(cert_path).unwrap_or_else(|why| {
+                eprintln!("Failed to read client certificate '{}': {}", cert_path, why);
+                exit(1);
+            });
+            let key_pem = fs::read(key_path).unwrap_or_else(|why| {
+                eprintln!("Failed to read client key '{}': {}", key_path, why);
+                exit(1);
+            });
+
+            let identity = Identity::from_pkcs8_pem(&cert_pem, &key_pem).unwrap_or_else(|why| {
+                eprintln!("Failed to build client identity: {}", why);
+                exit(1);
+            });
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => {
+            eprintln!("--client-cert and --client-key must be provided together");
+            exit(1);
+        }
+    }
+
+    builder.build().unwrap_or_else(|why| {
+        eprintln!("Failed to build HTTP client: {}", why);
+        exit(1);
+    })
+}
+
+fn decode_body(headers: &HeaderMap, bytes: &[u8]) -> String {
+    let encoding = headers
+        .get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+
+    let decoded = match encoding {
+        "gzip" => {
+            let mut out = Vec::new();
+            match GzDecoder::new(bytes).read_to_end(&mut out) {
+                Ok(_) => out,
+                Err(_) => bytes.to_vec(),
+            }
+        }
+        "deflate" => {
+            let mut out = Vec::new();
+            match DeflateDecoder::new(bytes).read_to_end(&mut out) {
+                Ok(_) => out,
+                Err(_) => bytes.to_vec(),
+            }
+        }
+        _ => bytes.to_vec(),
+    };
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Compiles `--expect-body-regex` once up front so an invalid pattern is
+/// rejected at startup instead of surfacing mid-run from inside a worker task.
+fn compile_expect_body_regex(args: &Args) -> Option<Regex> {
+    args.expect_body_regex.as_deref().map(|pattern| {
+        Regex::new(pattern).unwrap_or_else(|why| {
+            eprintln!("Invalid --expect-body-regex pattern: {}", why);
+            exit(1);
+        })
+    })
+}
+
+fn body_matches(args: &Args, expect_body_regex: &Option<Regex>, body: &str) -> bool {
+    if let Some(expected) = &args.expect_body {
+        if !body.contains(expected.as_str()) {
+            return false;
+        }
+    }
+
+    if let Some(regex) = expect_body_regex {
+        if !regex.is_match(body) {
+            return false;
+        }
+    }
+
+    true
+}
+
 fn build_request(args: Args, prefix: ColoredString) -> Request {
     let headers_map: HashMap<String, String> = match args.headers {
         Some(headers) => headers
@@ -220,6 +920,10 @@ fn build_request(args: Args, prefix: ColoredString) -> Request {
         }
     }
 
+    if let Some(timeout_ms) = args.timeout {
+        *request.timeout_mut() = Some(Duration::from_millis(timeout_ms));
+    }
+
     request
 }
 